@@ -17,6 +17,8 @@ enum ExecutorArg {
     LocalDocker,
     #[value(name = "local_process")]
     LocalProcess,
+    #[value(name = "local_sandbox")]
+    LocalSandbox,
     #[value(name = "remote")]
     Remote,
 }
@@ -26,11 +28,38 @@ impl From<ExecutorArg> for lab_runner::ExecutorKind {
         match value {
             ExecutorArg::LocalDocker => lab_runner::ExecutorKind::LocalDocker,
             ExecutorArg::LocalProcess => lab_runner::ExecutorKind::LocalProcess,
+            ExecutorArg::LocalSandbox => lab_runner::ExecutorKind::LocalSandbox,
             ExecutorArg::Remote => lab_runner::ExecutorKind::Remote,
         }
     }
 }
 
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum AutotuneGoalArg {
+    #[value(name = "minimize")]
+    Minimize,
+    #[value(name = "maximize")]
+    Maximize,
+}
+
+impl From<AutotuneGoalArg> for lab_runner::AutotuneGoal {
+    fn from(value: AutotuneGoalArg) -> Self {
+        match value {
+            AutotuneGoalArg::Minimize => lab_runner::AutotuneGoal::Minimize,
+            AutotuneGoalArg::Maximize => lab_runner::AutotuneGoal::Maximize,
+        }
+    }
+}
+
+impl std::fmt::Display for AutotuneGoalArg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AutotuneGoalArg::Minimize => write!(f, "minimize"),
+            AutotuneGoalArg::Maximize => write!(f, "maximize"),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, ValueEnum)]
 enum MaterializeArg {
     #[value(name = "none")]
@@ -54,6 +83,34 @@ impl From<MaterializeArg> for lab_runner::MaterializationMode {
     }
 }
 
+#[derive(Subcommand)]
+enum ResultsCommand {
+    List {
+        #[arg(long)]
+        json: bool,
+    },
+    Show {
+        #[arg(long)]
+        run_id: String,
+        #[arg(long)]
+        json: bool,
+    },
+    Delete {
+        #[arg(long)]
+        run_id: String,
+        #[arg(long)]
+        json: bool,
+    },
+    Prune {
+        #[arg(long)]
+        keep: Option<usize>,
+        #[arg(long)]
+        older_than: Option<String>,
+        #[arg(long)]
+        json: bool,
+    },
+}
+
 #[derive(Subcommand)]
 enum Commands {
     Run {
@@ -69,8 +126,25 @@ enum Commands {
         #[arg(long)]
         remote_token_env: Option<String>,
         #[arg(long)]
+        jobserver_tokens: Option<usize>,
+        #[arg(long)]
+        jobs: Option<usize>,
+        #[arg(long)]
+        fail_fast: bool,
+        #[arg(long)]
+        metrics_port: Option<u16>,
+        #[arg(long)]
         overrides: Option<PathBuf>,
         #[arg(long)]
+        junit: Option<PathBuf>,
+        #[arg(long)]
+        watch: bool,
+        /// Pin the randomized-scheduling seed for this run, overriding both the experiment's own
+        /// `design.policies.scheduling.seed` and `EXPERIMENT_SEED` -- use the seed a failed
+        /// trial's attempt log reported to replay its exact schedule.
+        #[arg(long)]
+        seed: Option<u64>,
+        #[arg(long)]
         json: bool,
     },
     RunDev {
@@ -87,6 +161,8 @@ enum Commands {
         #[arg(long)]
         overrides: Option<PathBuf>,
         #[arg(long)]
+        junit: Option<PathBuf>,
+        #[arg(long)]
         json: bool,
     },
     Replay {
@@ -97,6 +173,10 @@ enum Commands {
         #[arg(long)]
         strict: bool,
         #[arg(long)]
+        junit: Option<PathBuf>,
+        #[arg(long)]
+        watch: bool,
+        #[arg(long)]
         json: bool,
     },
     Fork {
@@ -104,12 +184,16 @@ enum Commands {
         run_dir: PathBuf,
         #[arg(long)]
         from_trial: String,
-        #[arg(long)]
-        at: String,
+        #[arg(long, required_unless_present = "watch")]
+        at: Option<String>,
         #[arg(long = "set")]
         set_values: Vec<String>,
         #[arg(long)]
         strict: bool,
+        /// Re-fork `from_trial` every time its harness script or `dataset/` directory changes,
+        /// always forking from the latest checkpoint rather than the fixed `--at` selector.
+        #[arg(long)]
+        watch: bool,
         #[arg(long)]
         json: bool,
     },
@@ -136,6 +220,29 @@ enum Commands {
         set_values: Vec<String>,
         #[arg(long)]
         strict: bool,
+        /// Resume every trial a host shutdown left `suspended` instead of one named trial.
+        #[arg(long)]
+        all: bool,
+        #[arg(long)]
+        json: bool,
+    },
+    Events {
+        #[arg(long)]
+        run_dir: PathBuf,
+        #[arg(long)]
+        trial_id: String,
+        #[arg(long)]
+        follow: bool,
+        #[arg(long)]
+        since: Option<u64>,
+        #[arg(long)]
+        json: bool,
+    },
+    Verify {
+        #[arg(long)]
+        run_dir: PathBuf,
+        #[arg(long)]
+        trial_id: String,
         #[arg(long)]
         json: bool,
     },
@@ -146,6 +253,49 @@ enum Commands {
         #[arg(long)]
         json: bool,
     },
+    ArchiveShow {
+        #[arg(long)]
+        run_dir: PathBuf,
+        #[arg(long)]
+        json: bool,
+    },
+    WorkspaceSnapshotShow {
+        /// A `workspace_pre_snapshot`/`workspace_post_snapshot` file from a trial's evidence
+        /// dir -- either the pretty-JSON or the packed `.rkyv` form `write_workspace_snapshot_manifest`
+        /// chose for it.
+        #[arg(long)]
+        path: PathBuf,
+        /// Look up a single file's entry instead of printing the whole manifest.
+        #[arg(long)]
+        lookup: Option<String>,
+        #[arg(long)]
+        json: bool,
+    },
+    LedgerVerify {
+        #[arg(long)]
+        run_dir: PathBuf,
+        #[arg(long)]
+        json: bool,
+    },
+    ReplayVerify {
+        #[arg(long)]
+        run_dir: PathBuf,
+        #[arg(long)]
+        trial_id: String,
+        #[arg(long)]
+        json: bool,
+    },
+    Snapshot {
+        experiment: PathBuf,
+        #[arg(long)]
+        overrides: Option<PathBuf>,
+        #[arg(long)]
+        snapshot: PathBuf,
+        #[arg(long)]
+        bless: bool,
+        #[arg(long)]
+        json: bool,
+    },
     KnobsInit {
         #[arg(long, default_value = ".lab/knobs/manifest.json")]
         manifest: PathBuf,
@@ -162,6 +312,44 @@ enum Commands {
         #[arg(long)]
         json: bool,
     },
+    Autotune {
+        experiment: PathBuf,
+        #[arg(long, default_value = ".lab/knobs/manifest.json")]
+        manifest: PathBuf,
+        #[arg(long)]
+        base_overrides: Option<PathBuf>,
+        #[arg(long, default_value = ".lab/knobs/overrides.json")]
+        out: PathBuf,
+        #[arg(long, value_enum, default_value_t = AutotuneGoalArg::Minimize)]
+        goal: AutotuneGoalArg,
+        #[arg(long, default_value_t = 30)]
+        max_trials: usize,
+        #[arg(long, default_value_t = 1e-3)]
+        tolerance: f64,
+        #[arg(long)]
+        container: bool,
+        #[arg(long)]
+        json: bool,
+    },
+    Search {
+        experiment: PathBuf,
+        #[arg(long = "param")]
+        params: Vec<String>,
+        #[arg(long)]
+        score_pointer: String,
+        #[arg(long, default_value = ".lab/search/result.json")]
+        out: PathBuf,
+        #[arg(long, value_enum, default_value_t = AutotuneGoalArg::Minimize)]
+        goal: AutotuneGoalArg,
+        #[arg(long, default_value_t = 30)]
+        max_evaluations: usize,
+        #[arg(long, default_value_t = 1e-3)]
+        tolerance: f64,
+        #[arg(long)]
+        container: bool,
+        #[arg(long)]
+        json: bool,
+    },
     SchemaValidate {
         #[arg(long)]
         schema: String,
@@ -186,6 +374,20 @@ enum Commands {
         #[arg(long)]
         json: bool,
     },
+    Lineage {
+        #[arg(long)]
+        run_dir: Option<PathBuf>,
+        #[arg(long)]
+        all_runs: bool,
+        #[arg(long)]
+        out: Option<PathBuf>,
+        #[arg(long)]
+        json: bool,
+    },
+    Results {
+        #[command(subcommand)]
+        action: ResultsCommand,
+    },
     Init {
         #[arg(long)]
         in_place: bool,
@@ -202,6 +404,12 @@ enum Commands {
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    if matches!(
+        cli.command,
+        Commands::Run { .. } | Commands::RunDev { .. } | Commands::RunExperiment { .. }
+    ) {
+        lab_runner::install_interrupt_handler()?;
+    }
     let json_mode = command_json_mode(&cli.command);
     let result = run_command(cli.command);
     match result {
@@ -211,11 +419,15 @@ fn main() -> Result<()> {
         }
         Ok(None) => Ok(()),
         Err(err) => {
+            let (code, details) = err
+                .downcast_ref::<lab_runner::LabError>()
+                .map(|e| (e.code, e.details.clone()))
+                .unwrap_or(("command_failed", json!({})));
             if json_mode {
-                emit_json(&json_error("command_failed", err.to_string(), json!({})));
+                emit_json(&json_error(code, err.to_string(), details));
                 std::process::exit(1);
             }
-            Err(err)
+            Err(anyhow::anyhow!("[{}] {}", code, err))
         }
     }
 }
@@ -229,9 +441,36 @@ fn run_command(command: Commands) -> Result<Option<Value>> {
             materialize,
             remote_endpoint,
             remote_token_env,
+            jobserver_tokens,
+            jobs,
+            fail_fast,
+            metrics_port,
             overrides,
+            junit,
+            watch,
+            seed,
             json,
         } => {
+            if watch {
+                let watch_options = lab_runner::WatchOptions {
+                    overrides_path: overrides,
+                    use_container: container,
+                    ..lab_runner::WatchOptions::default()
+                };
+                let iterations =
+                    lab_runner::watch_experiment(&experiment, &watch_options, |run| {
+                        print_watch_run(run, json);
+                    })?;
+                if json {
+                    return Ok(Some(json!({
+                        "ok": true,
+                        "command": "run",
+                        "watch": true,
+                        "iterations": iterations
+                    })));
+                }
+                return Ok(None);
+            }
             let summary =
                 lab_runner::describe_experiment_with_overrides(&experiment, overrides.as_deref())?;
             let execution = lab_runner::RunExecutionOptions {
@@ -239,6 +478,12 @@ fn run_command(command: Commands) -> Result<Option<Value>> {
                 materialize: materialize.map(Into::into),
                 remote_endpoint,
                 remote_token_env,
+                jobserver_tokens,
+                jobs,
+                fail_fast,
+                metrics_port,
+                scheduling_seed_override: seed,
+                ..lab_runner::RunExecutionOptions::default()
             };
             let result = lab_runner::run_experiment_with_options_and_overrides(
                 &experiment,
@@ -246,6 +491,9 @@ fn run_command(command: Commands) -> Result<Option<Value>> {
                 overrides.as_deref(),
                 execution.clone(),
             )?;
+            if let Some(junit_path) = &junit {
+                write_junit_report(junit_path, &summary.exp_id, &result.run_dir)?;
+            }
             if json {
                 return Ok(Some(json!({
                     "ok": true,
@@ -256,12 +504,21 @@ fn run_command(command: Commands) -> Result<Option<Value>> {
                     "executor": execution.executor.map(|e| e.as_str()),
                     "materialize": execution.materialize.map(|m| m.as_str()),
                     "remote_endpoint": execution.remote_endpoint,
-                    "remote_token_env": execution.remote_token_env
+                    "remote_token_env": execution.remote_token_env,
+                    "jobserver_tokens": execution.jobserver_tokens,
+                    "jobs": execution.jobs,
+                    "fail_fast": execution.fail_fast,
+                    "metrics_port": execution.metrics_port
                 })));
             }
             print_summary(&summary);
             println!("run_id: {}", result.run_id);
             println!("run_dir: {}", result.run_dir.display());
+            if result.interrupted {
+                println!("interrupted: true");
+                println!("checkpoint_acked: {}", result.checkpoint_acked);
+                println!("stop_acked: {}", result.stop_acked);
+            }
         }
         Commands::RunDev {
             experiment,
@@ -296,10 +553,16 @@ fn run_command(command: Commands) -> Result<Option<Value>> {
             println!("dev_network_mode: full");
             println!("run_id: {}", result.run_id);
             println!("run_dir: {}", result.run_dir.display());
+            if result.interrupted {
+                println!("interrupted: true");
+                println!("checkpoint_acked: {}", result.checkpoint_acked);
+                println!("stop_acked: {}", result.stop_acked);
+            }
         }
         Commands::RunExperiment {
             experiment,
             overrides,
+            junit,
             json,
         } => {
             let summary =
@@ -308,6 +571,9 @@ fn run_command(command: Commands) -> Result<Option<Value>> {
                 &experiment,
                 overrides.as_deref(),
             )?;
+            if let Some(junit_path) = &junit {
+                write_junit_report(junit_path, &summary.exp_id, &result.run_dir)?;
+            }
             if json {
                 return Ok(Some(json!({
                     "ok": true,
@@ -321,14 +587,68 @@ fn run_command(command: Commands) -> Result<Option<Value>> {
             println!("experiment_network_requirement: none");
             println!("run_id: {}", result.run_id);
             println!("run_dir: {}", result.run_dir.display());
+            if result.interrupted {
+                println!("interrupted: true");
+                println!("checkpoint_acked: {}", result.checkpoint_acked);
+                println!("stop_acked: {}", result.stop_acked);
+            }
         }
         Commands::Replay {
             run_dir,
             trial_id,
             strict,
+            junit,
+            watch,
             json,
         } => {
+            if watch {
+                let watch_options = lab_runner::TrialWatchOptions::default();
+                let iterations = lab_runner::watch_replay_trial(
+                    &run_dir,
+                    &trial_id,
+                    strict,
+                    &watch_options,
+                    |run| print_trial_watch_run(run, json),
+                )?;
+                if json {
+                    return Ok(Some(json!({
+                        "ok": true,
+                        "command": "replay",
+                        "watch": true,
+                        "iterations": iterations
+                    })));
+                }
+                return Ok(None);
+            }
             let result = lab_runner::replay_trial(&run_dir, &trial_id, strict)?;
+            if let Some(junit_path) = &junit {
+                let exp_id = resolved_experiment_id(&run_dir);
+                let stderr_path = result.replay_dir.join("trial_1").join("harness_stderr.log");
+                let stderr = std::fs::read_to_string(&stderr_path).unwrap_or_default();
+                let passed = result.harness_status == "0"
+                    && result.expectation_grade.as_ref().map(|g| g.pass).unwrap_or(true);
+                let failure = if passed {
+                    None
+                } else {
+                    Some(format!(
+                        "harness_status={} replay_grade={}",
+                        result.harness_status, result.replay_grade
+                    ))
+                };
+                let cases = vec![JunitCase {
+                    classname: result.parent_trial_id.clone(),
+                    name: result.replay_id.clone(),
+                    duration_seconds: 0.0,
+                    failure,
+                    system_err: stderr,
+                    metrics: result
+                        .matches
+                        .as_ref()
+                        .map(|m| m.metrics.clone())
+                        .unwrap_or_default(),
+                }];
+                std::fs::write(junit_path, junit_xml(&exp_id, &cases))?;
+            }
             if json {
                 return Ok(Some(json!({
                     "ok": true,
@@ -349,9 +669,31 @@ fn run_command(command: Commands) -> Result<Option<Value>> {
             at,
             set_values,
             strict,
+            watch,
             json,
         } => {
             let set_bindings = parse_set_bindings(&set_values)?;
+            if watch {
+                let watch_options = lab_runner::TrialWatchOptions::default();
+                let iterations = lab_runner::watch_fork_trial(
+                    &run_dir,
+                    &from_trial,
+                    &set_bindings,
+                    strict,
+                    &watch_options,
+                    |run| print_fork_watch_run(run, json),
+                )?;
+                if json {
+                    return Ok(Some(json!({
+                        "ok": true,
+                        "command": "fork",
+                        "watch": true,
+                        "iterations": iterations
+                    })));
+                }
+                return Ok(None);
+            }
+            let at = at.ok_or_else(|| anyhow::anyhow!("--at is required unless --watch is set"))?;
             let result = lab_runner::fork_trial(&run_dir, &from_trial, &at, &set_bindings, strict)?;
             if json {
                 return Ok(Some(json!({
@@ -405,9 +747,35 @@ fn run_command(command: Commands) -> Result<Option<Value>> {
             label,
             set_values,
             strict,
+            all,
             json,
         } => {
             let set_bindings = parse_set_bindings(&set_values)?;
+            if all {
+                if trial_id.is_some() || label.is_some() {
+                    return Err(anyhow::anyhow!(
+                        "resume --all cannot be combined with --trial-id or --label"
+                    ));
+                }
+                let result = lab_runner::resume_suspended_trials(&run_dir, &set_bindings, strict)?;
+                if json {
+                    return Ok(Some(json!({
+                        "ok": true,
+                        "command": "resume",
+                        "resume_all": resume_all_result_to_json(&result),
+                    })));
+                }
+                println!("run_id: {}", result.run_id);
+                for resumed in &result.resumed {
+                    println!("trial_id: {}", resumed.trial_id);
+                    println!("selector: {}", resumed.selector);
+                    println!("fork_id: {}", resumed.fork.fork_id);
+                    println!("fork_dir: {}", resumed.fork.fork_dir.display());
+                    println!("replay_grade: {}", resumed.fork.replay_grade);
+                    println!("harness_status: {}", resumed.fork.harness_status);
+                }
+                return Ok(None);
+            }
             let result = lab_runner::resume_run(
                 &run_dir,
                 trial_id.as_deref(),
@@ -429,6 +797,76 @@ fn run_command(command: Commands) -> Result<Option<Value>> {
             println!("replay_grade: {}", result.fork.replay_grade);
             println!("harness_status: {}", result.fork.harness_status);
         }
+        Commands::Events {
+            run_dir,
+            trial_id,
+            follow,
+            since,
+            json,
+        } => {
+            let result = lab_runner::follow_trial_events(
+                &run_dir,
+                &trial_id,
+                follow,
+                since,
+                |index, event| {
+                    if json {
+                        println!("{}", serde_json::to_string(&json!({
+                            "event_index": index,
+                            "event": event
+                        }))?);
+                    } else {
+                        println!(
+                            "[{}] {}",
+                            index,
+                            event
+                                .get("event_type")
+                                .and_then(|v| v.as_str())
+                                .unwrap_or("unknown")
+                        );
+                    }
+                    Ok(())
+                },
+            )?;
+            if !json {
+                println!("events_emitted: {}", result.events_emitted);
+                println!("terminal_reached: {}", result.terminal_reached);
+                println!("last_event_index: {}", result.last_event_index);
+            }
+        }
+        Commands::Verify {
+            run_dir,
+            trial_id,
+            json,
+        } => {
+            let result = lab_runner::verify_trial(&run_dir, &trial_id)?;
+            if json {
+                return Ok(Some(json!({
+                    "ok": true,
+                    "command": "verify",
+                    "trial_id": result.trial_id,
+                    "expectation_grade": result.expectation_grade.as_ref().map(expectation_grade_to_json),
+                    "matches": result.matches.as_ref().map(matcher_outcome_to_json),
+                })));
+            }
+            println!("trial_id: {}", result.trial_id);
+            match &result.expectation_grade {
+                Some(grade) => {
+                    println!("expectation_grade: {}", if grade.pass { "pass" } else { "fail" });
+                    for outcome in &grade.outcomes {
+                        println!(
+                            "  {}: {} ~= /{}/ -> {}",
+                            outcome.name,
+                            if outcome.passed { "pass" } else { "fail" },
+                            outcome.pattern,
+                            outcome.passed
+                        );
+                    }
+                }
+                None => println!("expectation_grade: none (no expectations declared)"),
+            }
+            print_matcher_outcome(result.matches.as_ref());
+        }
         Commands::Describe {
             experiment,
             overrides,
@@ -445,6 +883,278 @@ fn run_command(command: Commands) -> Result<Option<Value>> {
             }
             print_summary(&summary);
         }
+        Commands::ArchiveShow { run_dir, json } => {
+            let archive = lab_runner::TrialArchive::open(&run_dir)?;
+            let Some(archive) = archive else {
+                if json {
+                    return Ok(Some(json!({
+                        "ok": true,
+                        "command": "archive-show",
+                        "archive": null,
+                    })));
+                }
+                println!("archive: none (run has no archive.rkyv)");
+                return Ok(None);
+            };
+            let root = archive.root();
+            if json {
+                let trials: Vec<Value> = root
+                    .trials
+                    .iter()
+                    .map(|t| {
+                        json!({
+                            "trial_id": t.trial_id.as_str(),
+                            "variant_id": t.variant_id.as_str(),
+                            "status": t.status.as_str(),
+                            "outcome": t.outcome.as_ref().map(|s| s.as_str()),
+                            "duration_seconds": t.duration_seconds,
+                            "expectation_pass": t.expectation_pass,
+                            "metrics": t.metrics.iter().map(|(k, v)| (k.as_str().to_string(), *v)).collect::<std::collections::BTreeMap<_, _>>(),
+                        })
+                    })
+                    .collect();
+                return Ok(Some(json!({
+                    "ok": true,
+                    "command": "archive-show",
+                    "run_id": root.run_id.as_str(),
+                    "trials": trials,
+                })));
+            }
+            println!("run_id: {}", root.run_id.as_str());
+            for t in root.trials.iter() {
+                println!(
+                    "  {} [{}] status={} outcome={} duration_seconds={:.3}",
+                    t.trial_id.as_str(),
+                    t.variant_id.as_str(),
+                    t.status.as_str(),
+                    t.outcome.as_ref().map(|s| s.as_str()).unwrap_or("none"),
+                    t.duration_seconds
+                );
+            }
+        }
+        Commands::WorkspaceSnapshotShow { path, lookup, json } => {
+            let is_packed = path.extension().and_then(|e| e.to_str()) == Some("rkyv");
+            if is_packed {
+                let archive = lab_runner::WorkspaceSnapshotArchive::open(&path)?;
+                let root = archive.root();
+                if let Some(lookup) = lookup {
+                    let entry = archive.lookup(&lookup);
+                    if json {
+                        return Ok(Some(json!({
+                            "ok": true,
+                            "command": "workspace-snapshot-show",
+                            "format": "packed",
+                            "entry": entry.map(|e| json!({
+                                "path": e.path.as_str(),
+                                "kind": e.kind.as_str(),
+                                "digest": e.digest.as_str(),
+                                "size_bytes": e.size_bytes,
+                                "chunks": e.chunks.iter().map(|c| c.as_str().to_string()).collect::<Vec<_>>(),
+                                "mode": e.mode,
+                                "symlink_target": e.symlink_target.as_ref().map(|s| s.as_str()),
+                            })),
+                        })));
+                    }
+                    match entry {
+                        Some(e) => println!("{} kind={} digest={} size_bytes={}", e.path.as_str(), e.kind.as_str(), e.digest.as_str(), e.size_bytes),
+                        None => println!("no entry for {}", lookup),
+                    }
+                } else if json {
+                    let files: Vec<Value> = root
+                        .files
+                        .iter()
+                        .map(|e| {
+                            json!({
+                                "path": e.path.as_str(),
+                                "kind": e.kind.as_str(),
+                                "digest": e.digest.as_str(),
+                                "size_bytes": e.size_bytes,
+                                "mode": e.mode,
+                                "symlink_target": e.symlink_target.as_ref().map(|s| s.as_str()),
+                            })
+                        })
+                        .collect();
+                    return Ok(Some(json!({
+                        "ok": true,
+                        "command": "workspace-snapshot-show",
+                        "format": "packed",
+                        "schema_version": root.schema_version.as_str(),
+                        "file_count": root.file_count,
+                        "total_bytes": root.total_bytes,
+                        "root_digest": root.root_digest.as_str(),
+                        "files": files,
+                    })));
+                } else {
+                    println!(
+                        "format: packed ({} files, {} bytes, root_digest={})",
+                        root.file_count, root.total_bytes, root.root_digest.as_str()
+                    );
+                    for e in root.files.iter() {
+                        println!(
+                            "  {} kind={} digest={} size_bytes={}",
+                            e.path.as_str(),
+                            e.kind.as_str(),
+                            e.digest.as_str(),
+                            e.size_bytes
+                        );
+                    }
+                }
+            } else {
+                let manifest: Value = serde_json::from_slice(&std::fs::read(&path)?)?;
+                if let Some(lookup) = lookup {
+                    let entry = manifest
+                        .get("files")
+                        .and_then(|v| v.as_array())
+                        .into_iter()
+                        .flatten()
+                        .find(|row| row.get("path").and_then(|v| v.as_str()) == Some(lookup.as_str()))
+                        .cloned();
+                    if json {
+                        return Ok(Some(json!({
+                            "ok": true,
+                            "command": "workspace-snapshot-show",
+                            "format": "json",
+                            "entry": entry,
+                        })));
+                    }
+                    match entry {
+                        Some(e) => println!("{}", e),
+                        None => println!("no entry for {}", lookup),
+                    }
+                } else if json {
+                    return Ok(Some(json!({
+                        "ok": true,
+                        "command": "workspace-snapshot-show",
+                        "format": "json",
+                        "manifest": manifest,
+                    })));
+                } else {
+                    println!("format: json");
+                    println!("{}", serde_json::to_string_pretty(&manifest)?);
+                }
+            }
+        }
+        Commands::LedgerVerify { run_dir, json } => {
+            let result = lab_runner::verify_evidence_ledger(&run_dir)?;
+            let ok = result.is_ok();
+            if json {
+                return Ok(Some(json!({
+                    "ok": ok,
+                    "command": "ledger-verify",
+                    "run_id": result.run_id,
+                    "records_checked": result.records_checked,
+                    "chain_head": result.chain_head,
+                    "footer": result.footer,
+                    "broken_link": result.broken_link.as_ref().map(|b| json!({
+                        "line": b.line,
+                        "reason": b.reason,
+                    })),
+                })));
+            }
+            println!("run_id: {}", result.run_id.as_deref().unwrap_or("unknown"));
+            println!("records_checked: {}", result.records_checked);
+            println!("chain_head: {}", result.chain_head);
+            match &result.broken_link {
+                Some(broken) => println!("status: broken at line {} ({})", broken.line, broken.reason),
+                None => println!("status: ok"),
+            }
+            if !ok {
+                std::process::exit(1);
+            }
+        }
+        Commands::ReplayVerify { run_dir, trial_id, json } => {
+            let report = lab_runner::verify_trial_replay(&run_dir, &trial_id)?;
+            let ok = report.is_ok();
+            if json {
+                return Ok(Some(json!({
+                    "ok": ok,
+                    "command": "replay-verify",
+                    "trial_id": report.trial_id,
+                    "grade": report.grade,
+                    "invariants": report.invariants.iter().map(|i| json!({
+                        "name": i.name,
+                        "passed": i.passed,
+                        "detail": i.detail,
+                    })).collect::<Vec<_>>(),
+                })));
+            }
+            println!("trial_id: {}", report.trial_id);
+            println!("grade: {}", report.grade);
+            for invariant in &report.invariants {
+                println!(
+                    "  {} {}{}",
+                    if invariant.passed { "ok  " } else { "FAIL" },
+                    invariant.name,
+                    invariant
+                        .detail
+                        .as_deref()
+                        .map(|d| format!(" -- {}", d))
+                        .unwrap_or_default()
+                );
+            }
+            if !ok {
+                std::process::exit(1);
+            }
+        }
+        Commands::Snapshot {
+            experiment,
+            overrides,
+            snapshot,
+            bless,
+            json,
+        } => {
+            let summary =
+                lab_runner::describe_experiment_with_overrides(&experiment, overrides.as_deref())?;
+            let cwd = std::env::current_dir()?;
+            let actual = lab_runner::normalize_snapshot_value(&summary_to_json(&summary), &cwd);
+            let actual_text = serde_json::to_string_pretty(&actual)?;
+
+            if bless {
+                if let Some(parent) = snapshot.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(&snapshot, format!("{}\n", actual_text))?;
+                if json {
+                    return Ok(Some(json!({
+                        "ok": true,
+                        "command": "snapshot",
+                        "blessed": true,
+                        "snapshot": snapshot.display().to_string(),
+                    })));
+                }
+                println!("blessed: {}", snapshot.display());
+                return Ok(None);
+            }
+
+            if !snapshot.exists() {
+                return Err(anyhow::anyhow!(format!(
+                    "snapshot not found: {} (run with --bless to create it)",
+                    snapshot.display()
+                )));
+            }
+            let expected_text = std::fs::read_to_string(&snapshot)?;
+            let expected_trimmed = expected_text.trim_end();
+            let actual_trimmed = actual_text.trim_end();
+            let matched = expected_trimmed == actual_trimmed;
+            let diff = lab_runner::diff_snapshot_lines(expected_trimmed, actual_trimmed);
+
+            if json {
+                return Ok(Some(json!({
+                    "ok": matched,
+                    "command": "snapshot",
+                    "matched": matched,
+                    "snapshot": snapshot.display().to_string(),
+                    "diff": diff.iter().map(snapshot_diff_line_to_json).collect::<Vec<_>>(),
+                })));
+            }
+            if matched {
+                println!("snapshot: match ({})", snapshot.display());
+            } else {
+                println!("snapshot: MISMATCH ({})", snapshot.display());
+                print_snapshot_diff(&diff);
+                return Err(anyhow::anyhow!("snapshot mismatch"));
+            }
+        }
         Commands::KnobsInit {
             manifest,
             overrides,
@@ -476,6 +1186,93 @@ fn run_command(command: Commands) -> Result<Option<Value>> {
             }
             println!("ok");
         }
+        Commands::Autotune {
+            experiment,
+            manifest,
+            base_overrides,
+            out,
+            goal,
+            max_trials,
+            tolerance,
+            container,
+            json,
+        } => {
+            let options = lab_runner::AutotuneOptions {
+                use_container: container,
+                base_overrides_path: base_overrides,
+                goal: goal.into(),
+                max_trials,
+                tolerance,
+            };
+            let result =
+                lab_runner::autotune_experiment(&experiment, &manifest, &out, &options)?;
+            if json {
+                return Ok(Some(json!({
+                    "ok": true,
+                    "command": "autotune",
+                    "knob_ids": result.knob_ids,
+                    "best_values": result.best_values,
+                    "best_objective": result.best_objective,
+                    "trials": result.trials.iter().map(|t| json!({
+                        "trial": t.trial,
+                        "values": t.values,
+                        "objective": t.objective
+                    })).collect::<Vec<_>>(),
+                    "overrides_path": result.overrides_path.display().to_string()
+                })));
+            }
+            println!("best_objective: {}", result.best_objective);
+            println!("overrides: {}", result.overrides_path.display());
+            for (id, value) in &result.best_values {
+                if result.knob_ids.iter().any(|k| k == id) {
+                    println!("  {} = {}", id, value);
+                }
+            }
+        }
+        Commands::Search {
+            experiment,
+            params,
+            score_pointer,
+            out,
+            goal,
+            max_evaluations,
+            tolerance,
+            container,
+            json,
+        } => {
+            let params = parse_search_params(&params)?;
+            let options = lab_runner::SearchOptions {
+                use_container: container,
+                goal: goal.into(),
+                max_evaluations,
+                tolerance,
+            };
+            let result =
+                lab_runner::search_trial_params(&experiment, &params, &score_pointer, &out, &options)?;
+            if json {
+                return Ok(Some(json!({
+                    "ok": true,
+                    "command": "search",
+                    "param_ids": result.param_ids,
+                    "best_params": result.best_params,
+                    "best_score": result.best_score,
+                    "best_trial_dir": result.best_trial_dir.display().to_string(),
+                    "evaluations": result.evaluations.iter().map(|e| json!({
+                        "evaluation": e.evaluation,
+                        "params": e.params,
+                        "score": e.score,
+                        "trial_dir": e.trial_dir.display().to_string()
+                    })).collect::<Vec<_>>(),
+                    "result_path": result.result_path.display().to_string()
+                })));
+            }
+            println!("best_score: {}", result.best_score);
+            println!("best_trial_dir: {}", result.best_trial_dir.display());
+            println!("result: {}", result.result_path.display());
+            for (id, value) in &result.best_params {
+                println!("  {} = {}", id, value);
+            }
+        }
         Commands::SchemaValidate { schema, file, json } => {
             let compiled = lab_schemas::compile_schema(&schema)?;
             let data = std::fs::read_to_string(file)?;
@@ -529,6 +1326,135 @@ fn run_command(command: Commands) -> Result<Option<Value>> {
             }
             println!("bundle: {}", out_path.display());
         }
+        Commands::Lineage {
+            run_dir,
+            all_runs,
+            out,
+            json,
+        } => {
+            let graph = if all_runs {
+                let project_root = std::env::current_dir()?;
+                lab_runner::collect_project_lineage(&project_root)?
+            } else {
+                let run_dir = run_dir.ok_or_else(|| {
+                    anyhow::anyhow!("lineage requires --run-dir unless --all-runs is set")
+                })?;
+                lab_runner::collect_lineage(&run_dir)?
+            };
+            let dot = lab_runner::render_lineage_dot(&graph);
+            if let Some(out_path) = &out {
+                std::fs::write(out_path, &dot)?;
+            }
+            if json {
+                return Ok(Some(json!({
+                    "ok": true,
+                    "command": "lineage",
+                    "nodes": graph.nodes.len(),
+                    "edges": graph.edges.len(),
+                    "out": out.as_ref().map(|p| p.display().to_string()),
+                    "dot": dot
+                })));
+            }
+            if out.is_none() {
+                print!("{}", dot);
+            } else {
+                println!("lineage: {}", out.unwrap().display());
+            }
+        }
+        Commands::Results { action } => match action {
+            ResultsCommand::List { json } => {
+                let project_root = std::env::current_dir()?;
+                let runs = lab_runner::list_runs(&project_root)?;
+                if json {
+                    return Ok(Some(json!({
+                        "ok": true,
+                        "command": "results-list",
+                        "runs": runs.iter().map(run_index_to_json).collect::<Vec<_>>()
+                    })));
+                }
+                for r in &runs {
+                    println!(
+                        "{}  status={}  experiment={}  trials={}",
+                        r.run_id,
+                        r.status,
+                        r.experiment_id.as_deref().unwrap_or("-"),
+                        r.trial_count
+                    );
+                }
+            }
+            ResultsCommand::Show { run_id, json } => {
+                let project_root = std::env::current_dir()?;
+                let summary = lab_runner::show_run(&project_root, &run_id)?;
+                if json {
+                    return Ok(Some(json!({
+                        "ok": true,
+                        "command": "results-show",
+                        "run": run_index_to_json(&summary.index),
+                        "active_trial": summary.active_trial,
+                        "pause_label": summary.pause_label,
+                        "trials": summary.trials.iter().map(|t| json!({
+                            "trial_id": t.trial_id,
+                            "status": t.status,
+                            "outcome": t.outcome
+                        })).collect::<Vec<_>>()
+                    })));
+                }
+                println!("run_id: {}", summary.index.run_id);
+                println!("status: {}", summary.index.status);
+                println!(
+                    "experiment: {}",
+                    summary.index.experiment_id.as_deref().unwrap_or("-")
+                );
+                if let Some(active_trial) = summary.active_trial.as_deref() {
+                    println!(
+                        "active_trial: {}{}",
+                        active_trial,
+                        summary
+                            .pause_label
+                            .as_deref()
+                            .map(|l| format!(" (pause_label={})", l))
+                            .unwrap_or_default()
+                    );
+                }
+                println!("trials: {}", summary.trials.len());
+                for t in &summary.trials {
+                    println!(
+                        "  {} status={} outcome={}",
+                        t.trial_id,
+                        t.status,
+                        t.outcome.as_deref().unwrap_or("-")
+                    );
+                }
+            }
+            ResultsCommand::Delete { run_id, json } => {
+                let project_root = std::env::current_dir()?;
+                lab_runner::delete_run(&project_root, &run_id)?;
+                if json {
+                    return Ok(Some(
+                        json!({"ok": true, "command": "results-delete", "run_id": run_id}),
+                    ));
+                }
+                println!("deleted: {}", run_id);
+            }
+            ResultsCommand::Prune {
+                keep,
+                older_than,
+                json,
+            } => {
+                let project_root = std::env::current_dir()?;
+                let older_than_duration = older_than.as_deref().map(parse_duration).transpose()?;
+                let deleted = lab_runner::prune_runs(&project_root, keep, older_than_duration)?;
+                if json {
+                    return Ok(Some(
+                        json!({"ok": true, "command": "results-prune", "deleted": deleted}),
+                    ));
+                }
+                println!("deleted: {}", deleted.len());
+                for run_id in &deleted {
+                    println!("  {}", run_id);
+                }
+            }
+        },
         Commands::Init { in_place, force } => {
             let cwd = std::env::current_dir()?;
             let root = cwd;
@@ -590,6 +1516,12 @@ runtime:
 validity:
   fail_on_state_leak: true
   fail_on_profile_invariant_violation: true
+expectations: {}                      # OPTIONAL: stream/output name -> [regex, ...]
+                                       # patterns are regexes, not globs: escape metacharacters
+                                       # (. + * ? ( ) | [ ] { } ^ $ # & - ~) for literal matches
+matchers: []                          # OPTIONAL: list of {name, target: metric|diagnostic,
+                                       # source: stdout|stderr|both, patterns: [{regex, groups}]}
+                                       # groups maps value/metric/severity/code -> capture name or index
 ";
             std::fs::write(&exp_path, exp_yaml)?;
 
@@ -655,24 +1587,220 @@ fn command_json_mode(command: &Commands) -> bool {
         | Commands::RunExperiment { json, .. }
         | Commands::Replay { json, .. }
         | Commands::Fork { json, .. }
+        | Commands::Events { json, .. }
+        | Commands::Verify { json, .. }
         | Commands::Pause { json, .. }
         | Commands::Resume { json, .. }
         | Commands::Describe { json, .. }
+        | Commands::ArchiveShow { json, .. }
+        | Commands::WorkspaceSnapshotShow { json, .. }
+        | Commands::LedgerVerify { json, .. }
+        | Commands::ReplayVerify { json, .. }
+        | Commands::Snapshot { json, .. }
         | Commands::KnobsValidate { json, .. }
+        | Commands::Autotune { json, .. }
+        | Commands::Search { json, .. }
         | Commands::SchemaValidate { json, .. }
         | Commands::HooksValidate { json, .. }
-        | Commands::Publish { json, .. } => *json,
+        | Commands::Publish { json, .. }
+        | Commands::Lineage { json, .. } => *json,
+        Commands::Results { action } => match action {
+            ResultsCommand::List { json } => *json,
+            ResultsCommand::Show { json, .. } => *json,
+            ResultsCommand::Delete { json, .. } => *json,
+            ResultsCommand::Prune { json, .. } => *json,
+        },
         _ => false,
     }
 }
 
+/// One `<testcase>` worth of JUnit data, independent of whether it came from a full run's
+/// trials or a single replay.
+struct JunitCase {
+    classname: String,
+    name: String,
+    duration_seconds: f64,
+    failure: Option<String>,
+    system_err: String,
+    metrics: std::collections::BTreeMap<String, f64>,
+}
+
+fn xml_escape(raw: &str) -> String {
+    raw.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn junit_xml(suite_name: &str, cases: &[JunitCase]) -> String {
+    let failures = cases.iter().filter(|c| c.failure.is_some()).count();
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(&format!(
+        "<testsuite name=\"{}\" tests=\"{}\" failures=\"{}\">\n",
+        xml_escape(suite_name),
+        cases.len(),
+        failures
+    ));
+    for case in cases {
+        out.push_str(&format!(
+            "  <testcase classname=\"{}\" name=\"{}\" time=\"{:.3}\">\n",
+            xml_escape(&case.classname),
+            xml_escape(&case.name),
+            case.duration_seconds
+        ));
+        if !case.metrics.is_empty() {
+            out.push_str("    <properties>\n");
+            for (name, value) in &case.metrics {
+                out.push_str(&format!(
+                    "      <property name=\"{}\" value=\"{}\"/>\n",
+                    xml_escape(name),
+                    value
+                ));
+            }
+            out.push_str("    </properties>\n");
+        }
+        if let Some(message) = &case.failure {
+            out.push_str(&format!(
+                "    <failure message=\"{}\"/>\n",
+                xml_escape(message)
+            ));
+        }
+        if !case.system_err.is_empty() {
+            out.push_str(&format!(
+                "    <system-err>{}</system-err>\n",
+                xml_escape(&case.system_err)
+            ));
+        }
+        out.push_str("  </testcase>\n");
+    }
+    out.push_str("</testsuite>\n");
+    out
+}
+
+/// Reads `/experiment/id` back out of a run's `resolved_experiment.json`, falling back to
+/// `"unknown"` so a missing/unreadable file doesn't block report generation.
+fn resolved_experiment_id(run_dir: &std::path::Path) -> String {
+    std::fs::read_to_string(run_dir.join("resolved_experiment.json"))
+        .ok()
+        .and_then(|s| serde_json::from_str::<Value>(&s).ok())
+        .and_then(|v| {
+            v.pointer("/experiment/id")
+                .and_then(|x| x.as_str().map(String::from))
+        })
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Builds a JUnit XML report for every trial under `run_dir` and writes it to `path`, so
+/// `run`/`run-experiment` can drop into CI test panes alongside the existing JSON output.
+fn write_junit_report(path: &std::path::Path, exp_id: &str, run_dir: &std::path::Path) -> Result<()> {
+    let trials = lab_runner::collect_trial_report(run_dir)?;
+    let cases = trials
+        .into_iter()
+        .map(|t| {
+            let passed = t.status == "completed" && t.expectation_pass.unwrap_or(true);
+            let failure = if passed {
+                None
+            } else {
+                Some(format!(
+                    "status={} outcome={} expectation_pass={}",
+                    t.status,
+                    t.outcome.as_deref().unwrap_or("unknown"),
+                    t.expectation_pass
+                        .map(|p| p.to_string())
+                        .unwrap_or_else(|| "n/a".to_string())
+                ))
+            };
+            JunitCase {
+                classname: t.variant_id,
+                name: t.trial_id,
+                duration_seconds: t.duration_seconds,
+                failure,
+                system_err: t.stderr,
+                metrics: t.metrics,
+            }
+        })
+        .collect::<Vec<_>>();
+    std::fs::write(path, junit_xml(exp_id, &cases))?;
+    Ok(())
+}
+
 fn run_result_to_json(result: &lab_runner::RunResult) -> Value {
     json!({
         "run_id": result.run_id,
-        "run_dir": result.run_dir.display().to_string()
+        "run_dir": result.run_dir.display().to_string(),
+        "interrupted": result.interrupted,
+        "checkpoint_acked": result.checkpoint_acked,
+        "stop_acked": result.stop_acked
     })
 }
 
+fn watch_triggered_by_json(run: &lab_runner::WatchRun) -> Vec<Value> {
+    run.triggered_by
+        .iter()
+        .map(|p| Value::String(p.display().to_string()))
+        .collect()
+}
+
+fn watch_change_scope_str(scope: &lab_runner::WatchChangeScope) -> String {
+    match scope {
+        lab_runner::WatchChangeScope::Initial => "initial".to_string(),
+        lab_runner::WatchChangeScope::Dataset => "dataset".to_string(),
+        lab_runner::WatchChangeScope::PolicyOnly => "policy_only".to_string(),
+        lab_runner::WatchChangeScope::Variants(ids) => format!("variants:{}", ids.join(",")),
+        lab_runner::WatchChangeScope::Full => "full".to_string(),
+    }
+}
+
+fn watch_run_to_json(run: &lab_runner::WatchRun) -> Value {
+    match &run.outcome {
+        Ok(result) => json!({
+            "iteration": run.iteration,
+            "ok": true,
+            "summary": run.summary.as_ref().map(summary_to_json),
+            "run": run_result_to_json(result),
+            "triggered_by": watch_triggered_by_json(run),
+            "scope": watch_change_scope_str(&run.scope)
+        }),
+        Err(err) => json!({
+            "iteration": run.iteration,
+            "ok": false,
+            "summary": run.summary.as_ref().map(summary_to_json),
+            "error": err,
+            "triggered_by": watch_triggered_by_json(run),
+            "scope": watch_change_scope_str(&run.scope)
+        }),
+    }
+}
+
+fn print_watch_run(run: &lab_runner::WatchRun, json: bool) {
+    if json {
+        emit_json(&watch_run_to_json(run));
+        return;
+    }
+    match (&run.summary, &run.outcome) {
+        (Some(summary), Ok(result)) => {
+            print_summary(summary);
+            println!("run_id: {}", result.run_id);
+            println!("run_dir: {}", result.run_dir.display());
+        }
+        (_, Err(err)) => {
+            eprintln!("watch iteration {} failed: {}", run.iteration, err);
+        }
+    }
+    if !run.triggered_by.is_empty() {
+        let paths = run
+            .triggered_by
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!("triggered by: {}", paths);
+    }
+    println!("scope: {}", watch_change_scope_str(&run.scope));
+    println!("--- watching for changes (iteration {}) ---", run.iteration);
+}
+
 fn replay_result_to_json(result: &lab_runner::ReplayResult) -> Value {
     json!({
         "replay_id": result.replay_id,
@@ -681,9 +1809,178 @@ fn replay_result_to_json(result: &lab_runner::ReplayResult) -> Value {
         "strict": result.strict,
         "replay_grade": result.replay_grade,
         "harness_status": result.harness_status,
+        "expectation_grade": result.expectation_grade.as_ref().map(expectation_grade_to_json),
+        "matches": result.matches.as_ref().map(matcher_outcome_to_json),
+    })
+}
+
+fn trial_watch_run_to_json(run: &lab_runner::TrialWatchRun) -> Value {
+    let triggered_by: Vec<Value> = run
+        .triggered_by
+        .iter()
+        .map(|p| Value::String(p.display().to_string()))
+        .collect();
+    match &run.outcome {
+        Ok(result) => json!({
+            "iteration": run.iteration,
+            "ok": true,
+            "replay": replay_result_to_json(result),
+            "event_type_counts": run.event_type_counts,
+            "triggered_by": triggered_by
+        }),
+        Err(err) => json!({
+            "iteration": run.iteration,
+            "ok": false,
+            "error": err,
+            "event_type_counts": run.event_type_counts,
+            "triggered_by": triggered_by
+        }),
+    }
+}
+
+fn print_trial_watch_run(run: &lab_runner::TrialWatchRun, json: bool) {
+    if json {
+        emit_json(&trial_watch_run_to_json(run));
+        return;
+    }
+    match &run.outcome {
+        Ok(result) => {
+            println!("replay_id: {}", result.replay_id);
+            println!("outcome: harness_status={}", result.harness_status);
+        }
+        Err(err) => {
+            eprintln!("watch iteration {} failed: {}", run.iteration, err);
+        }
+    }
+    if !run.event_type_counts.is_empty() {
+        let counts = run
+            .event_type_counts
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!("event_type_counts: {}", counts);
+    }
+    if !run.triggered_by.is_empty() {
+        let paths = run
+            .triggered_by
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!("triggered by: {}", paths);
+    }
+    println!("--- watching for changes (iteration {}) ---", run.iteration);
+}
+
+fn fork_watch_run_to_json(run: &lab_runner::ForkWatchRun) -> Value {
+    let triggered_by: Vec<String> = run
+        .triggered_by
+        .iter()
+        .map(|p| p.display().to_string())
+        .collect();
+    match &run.outcome {
+        Ok(result) => json!({
+            "iteration": run.iteration,
+            "ok": true,
+            "fork": fork_result_to_json(result),
+            "triggered_by": triggered_by
+        }),
+        Err(err) => json!({
+            "iteration": run.iteration,
+            "ok": false,
+            "error": err,
+            "triggered_by": triggered_by
+        }),
+    }
+}
+
+fn print_fork_watch_run(run: &lab_runner::ForkWatchRun, json: bool) {
+    if json {
+        emit_json(&fork_watch_run_to_json(run));
+        return;
+    }
+    match &run.outcome {
+        Ok(result) => {
+            println!("fork_id: {}", result.fork_id);
+            println!("selector: {}", result.selector);
+            println!("outcome: harness_status={}", result.harness_status);
+        }
+        Err(err) => {
+            eprintln!("watch iteration {} failed: {}", run.iteration, err);
+        }
+    }
+    if !run.triggered_by.is_empty() {
+        let paths = run
+            .triggered_by
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!("triggered by: {}", paths);
+    }
+    println!("--- watching for changes (iteration {}) ---", run.iteration);
+}
+
+fn expectation_grade_to_json(grade: &lab_runner::ExpectationGrade) -> Value {
+    json!({
+        "pass": grade.pass,
+        "outcomes": grade.outcomes.iter().map(|o| json!({
+            "name": o.name,
+            "pattern": o.pattern,
+            "passed": o.passed
+        })).collect::<Vec<_>>()
     })
 }
 
+fn matcher_outcome_to_json(outcome: &lab_runner::MatcherOutcome) -> Value {
+    json!({
+        "metrics": outcome.metrics,
+        "diagnostics": outcome.diagnostics.iter().map(|d| json!({
+            "matcher": d.matcher,
+            "severity": d.severity,
+            "code": d.code,
+            "line": d.line
+        })).collect::<Vec<_>>()
+    })
+}
+
+fn print_matcher_outcome(outcome: Option<&lab_runner::MatcherOutcome>) {
+    let Some(outcome) = outcome else {
+        println!("matches: none (no matchers declared)");
+        return;
+    };
+    for (name, value) in &outcome.metrics {
+        println!("  metric {}: {}", name, value);
+    }
+    for diag in &outcome.diagnostics {
+        println!(
+            "  diagnostic [{}]: {} {}",
+            diag.matcher,
+            diag.severity.as_deref().unwrap_or("-"),
+            diag.code.as_deref().unwrap_or("-")
+        );
+    }
+}
+
+fn snapshot_diff_line_to_json(line: &lab_runner::SnapshotDiffLine) -> Value {
+    match line {
+        lab_runner::SnapshotDiffLine::Context(text) => json!({"kind": "context", "text": text}),
+        lab_runner::SnapshotDiffLine::Removed(text) => json!({"kind": "removed", "text": text}),
+        lab_runner::SnapshotDiffLine::Added(text) => json!({"kind": "added", "text": text}),
+    }
+}
+
+fn print_snapshot_diff(diff: &[lab_runner::SnapshotDiffLine]) {
+    for line in diff {
+        match line {
+            lab_runner::SnapshotDiffLine::Context(text) => println!("  {}", text),
+            lab_runner::SnapshotDiffLine::Removed(text) => println!("- {}", text),
+            lab_runner::SnapshotDiffLine::Added(text) => println!("+ {}", text),
+        }
+    }
+}
+
 fn fork_result_to_json(result: &lab_runner::ForkResult) -> Value {
     json!({
         "fork_id": result.fork_id,
@@ -695,6 +1992,8 @@ fn fork_result_to_json(result: &lab_runner::ForkResult) -> Value {
         "fallback_mode": result.fallback_mode,
         "replay_grade": result.replay_grade,
         "harness_status": result.harness_status,
+        "expectation_grade": result.expectation_grade.as_ref().map(expectation_grade_to_json),
+        "matches": result.matches.as_ref().map(matcher_outcome_to_json),
     })
 }
 
@@ -716,25 +2015,108 @@ fn resume_result_to_json(result: &lab_runner::ResumeResult) -> Value {
     })
 }
 
+fn resume_all_result_to_json(result: &lab_runner::ResumeAllResult) -> Value {
+    json!({
+        "run_id": result.run_id,
+        "resumed": result.resumed.iter().map(resume_result_to_json).collect::<Vec<_>>(),
+    })
+}
+
+fn run_index_to_json(entry: &lab_runner::RunIndexEntry) -> Value {
+    json!({
+        "run_id": entry.run_id,
+        "started_at": entry.started_at,
+        "experiment_id": entry.experiment_id,
+        "trial_count": entry.trial_count,
+        "status": entry.status,
+        "run_dir": entry.run_dir.display().to_string()
+    })
+}
+
+fn parse_duration(raw: &str) -> Result<std::time::Duration> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return Err(anyhow::anyhow!("invalid duration ''"));
+    }
+    let (num_part, unit_secs) = match raw.chars().last().unwrap() {
+        's' => (&raw[..raw.len() - 1], 1u64),
+        'm' => (&raw[..raw.len() - 1], 60u64),
+        'h' => (&raw[..raw.len() - 1], 3600u64),
+        'd' => (&raw[..raw.len() - 1], 86400u64),
+        'w' => (&raw[..raw.len() - 1], 604800u64),
+        _ => (raw, 1u64),
+    };
+    let n: u64 = num_part
+        .parse()
+        .map_err(|_| anyhow::anyhow!("invalid duration '{}': expected e.g. '7d', '24h'", raw))?;
+    Ok(std::time::Duration::from_secs(n * unit_secs))
+}
+
+/// Parses `--set key=value` and `--set key:=value` (raw-string, bypassing JSON-type
+/// coercion) into a map from dotted/bracketed binding path to value. Path syntax itself
+/// (dots, `[n]` indices) is validated when the path is applied against the trial input in
+/// `lab_runner::fork_trial`/`resume_run`, since that's where intermediate structure is known.
 fn parse_set_bindings(values: &[String]) -> Result<BTreeMap<String, Value>> {
     let mut out = BTreeMap::new();
     for raw in values {
-        let (key, val_raw) = raw
-            .split_once('=')
-            .ok_or_else(|| anyhow::anyhow!(format!("invalid --set '{}': expected k=v", raw)))?;
+        let (key, val_raw, force_string) = if let Some((k, v)) = raw.split_once(":=") {
+            (k, v, true)
+        } else if let Some((k, v)) = raw.split_once('=') {
+            (k, v, false)
+        } else {
+            return Err(anyhow::anyhow!(format!(
+                "invalid --set '{}': expected key=value or key:=value",
+                raw
+            )));
+        };
         if key.trim().is_empty() {
             return Err(anyhow::anyhow!(format!(
                 "invalid --set '{}': key cannot be empty",
                 raw
             )));
         }
-        let parsed =
-            serde_json::from_str::<Value>(val_raw).unwrap_or(Value::String(val_raw.to_string()));
+        let parsed = if force_string {
+            Value::String(val_raw.to_string())
+        } else {
+            serde_json::from_str::<Value>(val_raw).unwrap_or(Value::String(val_raw.to_string()))
+        };
         out.insert(key.to_string(), parsed);
     }
     Ok(out)
 }
 
+/// Parses repeated `--param id:min:max` flags into `SearchParam`s.
+fn parse_search_params(values: &[String]) -> Result<Vec<lab_runner::SearchParam>> {
+    let mut out = Vec::with_capacity(values.len());
+    for raw in values {
+        let parts: Vec<&str> = raw.splitn(3, ':').collect();
+        let [id, min_raw, max_raw] = parts[..] else {
+            return Err(anyhow::anyhow!(format!(
+                "invalid --param '{}': expected id:min:max",
+                raw
+            )));
+        };
+        if id.trim().is_empty() {
+            return Err(anyhow::anyhow!(format!(
+                "invalid --param '{}': id cannot be empty",
+                raw
+            )));
+        }
+        let minimum: f64 = min_raw
+            .parse()
+            .map_err(|_| anyhow::anyhow!(format!("invalid --param '{}': minimum is not a number", raw)))?;
+        let maximum: f64 = max_raw
+            .parse()
+            .map_err(|_| anyhow::anyhow!(format!("invalid --param '{}': maximum is not a number", raw)))?;
+        out.push(lab_runner::SearchParam {
+            id: id.to_string(),
+            minimum,
+            maximum,
+        });
+    }
+    Ok(out)
+}
+
 fn summary_to_json(summary: &lab_runner::ExperimentSummary) -> Value {
     json!({
         "experiment": summary.exp_id,